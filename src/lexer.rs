@@ -0,0 +1,238 @@
+// A small, grammar-agnostic tokenizer. It only knows how to chop a `&str`
+// into typed slices plus their position in the source; it has no notion of
+// s-expressions, list nesting, or escape decoding. The parser consumes this
+// token stream to decide how to dispatch, which keeps scanning separate
+// from the grammar and makes the lexer reusable on its own (e.g. for
+// syntax highlighting or formatting).
+
+// a byte-offset range into the original source a token (or value) came from
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub enum TokenKind {
+    LParen,
+    RParen,
+    Int,
+    Float,
+    Sym,
+    // `terminated` is false when the closing quote was never found; the
+    // lexer flags this rather than failing, leaving the decision of what
+    // to do about it to the parser
+    Str { terminated: bool },
+    Comment { terminated: bool },
+    // reader-macro prefixes: 'x, `x, ,x, ,@x. Tokenized on their own so the
+    // parser can dispatch on them instead of re-deriving them from raw text
+    Quote,
+    Quasiquote,
+    Unquote,
+    UnquoteSplicing,
+    // `#;`, which discards the s-expression that follows it; lexed as a
+    // single marker token rather than folded into `Comment` since what it
+    // discards is a whole datum, not a run of text
+    DatumComment,
+    Whitespace,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Lexer<'a> {
+        Lexer { input, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.pos >= self.input.len() {
+            return None;
+        }
+
+        let start = self.pos;
+        let rest = &self.input[start..];
+        let c = rest.chars().next().unwrap();
+
+        let (kind, len) = match c {
+            '(' => (TokenKind::LParen, 1),
+            ')' => (TokenKind::RParen, 1),
+            '"' => {
+                match find_string_end(rest) {
+                    Some(end) => (TokenKind::Str { terminated: true }, end),
+                    None => (TokenKind::Str { terminated: false }, rest.len()),
+                }
+            },
+            ';' => {
+                let len = scan_while(rest, |c| c != '\n');
+                (TokenKind::Comment { terminated: true }, len)
+            },
+            '#' if rest.starts_with("#|") => {
+                match find_block_comment_end(rest) {
+                    Some(end) => (TokenKind::Comment { terminated: true }, end),
+                    None => (TokenKind::Comment { terminated: false }, rest.len()),
+                }
+            },
+            '#' if rest.starts_with("#;") => (TokenKind::DatumComment, 2),
+            '\'' => (TokenKind::Quote, 1),
+            '`' => (TokenKind::Quasiquote, 1),
+            ',' if rest.starts_with(",@") => (TokenKind::UnquoteSplicing, 2),
+            ',' => (TokenKind::Unquote, 1),
+            c if c.is_whitespace() => {
+                let len = scan_while(rest, |c| c.is_whitespace());
+                (TokenKind::Whitespace, len)
+            },
+            c if c.is_ascii_digit() || c == '-' => {
+                let len = scan_while(rest, |c| c.is_ascii_digit() || c == '.' || c == '-');
+                let kind = if rest[..len].contains('.') { TokenKind::Float } else { TokenKind::Int };
+                (kind, len)
+            },
+            _ => {
+                let len = scan_while(rest, |c| c != '(' && c != ')' && c != '"' && c != ';' && !c.is_whitespace());
+                (TokenKind::Sym, len)
+            },
+        };
+
+        let end = start + len;
+        self.pos = end;
+        Some(Token { kind, text: &self.input[start..end], span: Span { start, end } })
+    }
+}
+
+// byte length of the longest prefix of `s` all matching `pred`
+fn scan_while<F>(s: &str, pred: F) -> usize
+    where F: Fn(char) -> bool {
+
+    s.char_indices()
+        .find(|&(_, c)| !pred(c))
+        .map_or(s.len(), |(i, _)| i)
+}
+
+// byte length of a `"..."` string starting at the front of `s`, honoring
+// backslash escapes so an escaped quote doesn't end the token early; None
+// if the closing quote is never found
+fn find_string_end(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some(i + 1),
+            '\\' => { chars.next(); },
+            _ => {},
+        }
+    }
+
+    None
+}
+
+// byte length of a `#|...|#` block comment starting at the front of `s`,
+// tracking nesting depth; None if it's never closed
+fn find_block_comment_end(s: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut chars = s.char_indices().skip(2).peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if c == '#' && chars.peek().is_some_and(|&(_, next)| next == '|') {
+            chars.next();
+            depth += 1;
+        } else if c == '|' && chars.peek().is_some_and(|&(_, next)| next == '#') {
+            let (end, _) = chars.next().unwrap();
+            depth -= 1;
+            if depth == 0 {
+                return Some(end + 1);
+            }
+        }
+    }
+
+    None
+}
+
+
+#[cfg(test)]
+mod lexer_tests {
+    use super::*;
+
+    #[test]
+    fn lexes_atoms() {
+        let tokens: Vec<Token> = Lexer::new("foo 42 3.5").collect();
+        assert_eq!(tokens[0], Token { kind: TokenKind::Sym, text: "foo", span: Span { start: 0, end: 3 } });
+        assert_eq!(tokens[1], Token { kind: TokenKind::Whitespace, text: " ", span: Span { start: 3, end: 4 } });
+        assert_eq!(tokens[2], Token { kind: TokenKind::Int, text: "42", span: Span { start: 4, end: 6 } });
+        assert_eq!(tokens[3], Token { kind: TokenKind::Whitespace, text: " ", span: Span { start: 6, end: 7 } });
+        assert_eq!(tokens[4], Token { kind: TokenKind::Float, text: "3.5", span: Span { start: 7, end: 10 } });
+    }
+
+    #[test]
+    fn lexes_parens() {
+        let tokens: Vec<Token> = Lexer::new("(a)").collect();
+        assert_eq!(tokens[0].kind, TokenKind::LParen);
+        assert_eq!(tokens[1].kind, TokenKind::Sym);
+        assert_eq!(tokens[2].kind, TokenKind::RParen);
+    }
+
+    #[test]
+    fn lexes_strings_honoring_escapes() {
+        let tokens: Vec<Token> = Lexer::new("\"a\\\"b\" rest").collect();
+        assert_eq!(tokens[0], Token {
+            kind: TokenKind::Str { terminated: true },
+            text: "\"a\\\"b\"",
+            span: Span { start: 0, end: 6 },
+        });
+    }
+
+    #[test]
+    fn flags_unterminated_strings_and_comments() {
+        let tokens: Vec<Token> = Lexer::new("\"no end").collect();
+        assert_eq!(tokens[0].kind, TokenKind::Str { terminated: false });
+
+        let tokens: Vec<Token> = Lexer::new("#| no end").collect();
+        assert_eq!(tokens[0].kind, TokenKind::Comment { terminated: false });
+    }
+
+    #[test]
+    fn lexes_comments() {
+        let tokens: Vec<Token> = Lexer::new("; a line comment").collect();
+        assert_eq!(tokens[0].kind, TokenKind::Comment { terminated: true });
+
+        let tokens: Vec<Token> = Lexer::new("#| a #| nested |# comment |#").collect();
+        assert_eq!(tokens[0], Token {
+            kind: TokenKind::Comment { terminated: true },
+            text: "#| a #| nested |# comment |#",
+            span: Span { start: 0, end: 28 },
+        });
+    }
+
+    #[test]
+    fn lexes_reader_macro_prefixes() {
+        assert_eq!(Lexer::new("'x").next().unwrap().kind, TokenKind::Quote);
+        assert_eq!(Lexer::new("`x").next().unwrap().kind, TokenKind::Quasiquote);
+        assert_eq!(Lexer::new(",x").next().unwrap().kind, TokenKind::Unquote);
+
+        let tokens: Vec<Token> = Lexer::new(",@x").collect();
+        assert_eq!(tokens[0], Token { kind: TokenKind::UnquoteSplicing, text: ",@", span: Span { start: 0, end: 2 } });
+        assert_eq!(tokens[1].kind, TokenKind::Sym);
+    }
+
+    #[test]
+    fn lexes_datum_comment_marker() {
+        let tokens: Vec<Token> = Lexer::new("#;1").collect();
+        assert_eq!(tokens[0], Token { kind: TokenKind::DatumComment, text: "#;", span: Span { start: 0, end: 2 } });
+        assert_eq!(tokens[1].kind, TokenKind::Int);
+    }
+}
@@ -0,0 +1,8 @@
+// s-parse: a small, panic-free S-expression parser with source-span
+// tracking. `parse` is the crate's entry point; `lexer` is exposed for
+// callers (syntax highlighters, formatters) that only need tokens.
+
+pub mod lexer;
+pub mod parse;
+
+pub use parse::{parse, ParseError, SExpr, SExprKind, Span};
@@ -1,9 +1,10 @@
-#[macro_use] extern crate lazy_static;
-extern crate regex;
-mod parse;
+use s_parse::parse;
 
 fn main() {
     // an example usage
     let s = "((lambda (x) (* x x)) 50)";
-    println!("Parsed \"{}\" as {:?}", s, parse::parse(&s));
+    match parse(s) {
+        Ok(exprs) => println!("Parsed \"{}\" as {:?}", s, exprs),
+        Err(e) => println!("Failed to parse \"{}\": {:?}", s, e),
+    }
 }
\ No newline at end of file
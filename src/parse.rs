@@ -1,247 +1,399 @@
-use regex::Regex;
+use std::borrow::Cow;
+use std::iter::Peekable;
+use crate::lexer::{Lexer, Token, TokenKind};
+
+// re-exported so callers can keep writing `parse::Span`; the lexer owns
+// the definition since byte positions are fundamentally a lexing concern
+pub use crate::lexer::Span;
 
 #[derive(Debug)]
 #[derive(PartialEq)]
-pub enum SExpr<'a> {
+pub enum SExprKind<'a> {
     SInt(i32),
     SFloat(f32),
     SSym(&'a str),
-    SStr(&'a str),
+    SStr(Cow<'a, str>),
+    SChar(char),
+    SBool(bool),
     SList(Vec<SExpr<'a>>)
 }
 
 #[derive(Debug)]
-#[derive(PartialEq)]
-struct ParseResult<'a> {
-    parsed: SExpr<'a>,
-    rest: &'a str
+pub struct SExpr<'a> {
+    kind: SExprKind<'a>,
+    span: Span,
 }
 
-// parse 0 or more s-expressions from the input string
-pub fn parse(s: &str) -> Vec<SExpr> {
-    let mut copy = eat_whitespace(&s);
-    let mut exprs = Vec::new();
+impl<'a> SExpr<'a> {
+    fn new(kind: SExprKind<'a>, span: Span) -> SExpr<'a> {
+        SExpr { kind, span }
+    }
 
-    while !copy.is_empty() {
-        let res = s_parse(copy);            // parse an s-expression
-        exprs.push(res.parsed);             // add to expression vector
-        copy = eat_whitespace(res.rest);    // move to end of previous expr
+    // the parsed value itself
+    pub fn kind(&self) -> &SExprKind<'a> {
+        &self.kind
     }
 
-    return exprs;
+    // where in the source this value was parsed from
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
 }
 
-// parse an s-expression off the input
-fn s_parse(s: &str) -> ParseResult {
-    // decide how to parse from first char
-    match s.chars().next() {
-        Some(c) => {
-            if c.is_digit(10) || c == '-' {
-                num_parse(s)
-            } else if c == '"' {
-                str_parse(s)
-            } else if c == '(' {
-                list_parse(s)
-            } else {
-                sym_parse(s)
-            }
-        },
-        None => panic!("s_parse: can't parse s-expr from empty input"),
+// equality is over parsed structure; where an expression came from doesn't
+// affect its value
+impl<'a> PartialEq for SExpr<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
     }
 }
 
-// determine slice from front of input string containing a particular token
-fn read_until<F>(s: &str, stop_condition: F) -> (&str, &str) 
-    where F: Fn(char) -> bool {
+// errors that can occur while parsing an s-expression
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum ParseError {
+    UnexpectedEof,
+    UnexpectedChar(char),
+    BadNumber(String),
+    UnterminatedString,
+    UnterminatedList,
+    UnterminatedComment,
+    BadEscape(String),
+    BadChar(String),
+}
 
-    let mut tok_end = 0;
-    let len = s.len();
+// a single pass over the source, shared by every parser function below so
+// each token is scanned exactly once; `peek` lets `s_parse` decide how to
+// dispatch before a sub-parser commits to consuming the token
+type Tokens<'a> = Peekable<Lexer<'a>>;
 
-    // scan until stop condition met
-    for (i, c) in s.chars().enumerate() {
-        if !stop_condition(c) {
-            // if read to end of input, end idx is str length
-            if i == len - 1 { tok_end = len; }
-            continue;
-        } else {
-            tok_end = i;
-            break;
-        }
+// parse 0 or more s-expressions from the input string
+pub fn parse(s: &str) -> Result<Vec<SExpr<'_>>, ParseError> {
+    let mut tokens = Lexer::new(s).peekable();
+    let mut exprs = Vec::new();
+
+    skip_trivia(&mut tokens)?;
+    while tokens.peek().is_some() {
+        exprs.push(s_parse(&mut tokens)?);
+        skip_trivia(&mut tokens)?;
     }
 
-    // return (scanned token, rest of input)
-    (&s[..tok_end], &s[tok_end..])
+    Ok(exprs)
 }
 
-// parse a number off the input
-fn num_parse(s: &str) -> ParseResult {
-    // read until non-(digit/sign/decimal) char encountered
-    let (num_slice, rest_slice) = read_until(s, |c| {
-        !(c.is_digit(10) || c == '.' || c == '-')
-    });
+// parse an s-expression off the token stream, dispatching on the next token
+fn s_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let token = match tokens.peek().copied() {
+        Some(t) => t,
+        None => return Err(ParseError::UnexpectedEof),
+    };
+
+    match token.kind {
+        TokenKind::Quote => quote_parse(tokens, "quote"),
+        TokenKind::Quasiquote => quote_parse(tokens, "quasiquote"),
+        TokenKind::Unquote => quote_parse(tokens, "unquote"),
+        TokenKind::UnquoteSplicing => quote_parse(tokens, "unquote-splicing"),
+        TokenKind::Int | TokenKind::Float => num_parse(tokens),
+        TokenKind::Str { .. } => str_parse(tokens),
+        TokenKind::LParen => list_parse(tokens),
+        TokenKind::Sym if token.text.starts_with('#') => hash_parse(tokens),
+        TokenKind::Sym => sym_parse(tokens),
+        _ => Err(ParseError::UnexpectedChar(token.text.chars().next().unwrap())),
+    }
+}
+
+// parse a number off the token stream
+fn num_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let token = tokens.next().ok_or(ParseError::UnexpectedEof)?;
 
     // attempt to parse int, then try float on fail
-    match num_slice.parse::<i32>() {
-        Ok(int_val) => 
-            ParseResult { 
-                parsed: SExpr::SInt(int_val),
-                rest: rest_slice
-            },
+    match token.text.parse::<i32>() {
+        Ok(int_val) => Ok(SExpr::new(SExprKind::SInt(int_val), token.span)),
         Err(_) => {
-            match num_slice.parse::<f32>() {
-                Ok(float_val) => 
-                    ParseResult { 
-                        parsed: SExpr::SFloat(float_val),
-                        rest: rest_slice
-                    },
-                Err(_) => panic!("num_parse: expected numeric value, got: \"{}\"", 
-                            if num_slice.is_empty() { rest_slice } else { num_slice }),
+            match token.text.parse::<f32>() {
+                Ok(float_val) => Ok(SExpr::new(SExprKind::SFloat(float_val), token.span)),
+                Err(_) => Err(ParseError::BadNumber(token.text.to_string())),
             }
         }
     }
 }
 
-// parse a symbol off the input
-fn sym_parse(s: &str) -> ParseResult {
-    // read chars until space/closing parenthesis encountered
-    let (sym_slice, rest_slice) = read_until(s, |c| {
-        c == ' ' || c == ')'
-    });
+// parse a symbol off the token stream
+fn sym_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let token = match tokens.next() {
+        Some(t) if t.kind == TokenKind::Sym => t,
+        Some(t) => return Err(ParseError::UnexpectedChar(t.text.chars().next().unwrap())),
+        None => return Err(ParseError::UnexpectedEof),
+    };
 
-    if sym_slice.is_empty() {
-        panic!("sym_parse: expected symbol, got: \"{}\"", rest_slice);
-    }
+    Ok(SExpr::new(SExprKind::SSym(token.text), token.span))
+}
+
+// desugar a reader macro prefix token (already peeked, not yet consumed)
+// into the corresponding `(sym expr)` list, e.g. `'x` -> `(quote x)`
+fn quote_parse<'a>(tokens: &mut Tokens<'a>, sym: &'static str) -> Result<SExpr<'a>, ParseError> {
+    let prefix = tokens.next().expect("checked by s_parse's dispatch");
+    skip_trivia(tokens)?; // Scheme allows whitespace/comments before the quoted datum
+    let inner = s_parse(tokens)?;
+    let span = Span { start: prefix.span.start, end: inner.get_span().end };
+    let tag = SExpr::new(SExprKind::SSym(sym), prefix.span);
+
+    Ok(SExpr::new(SExprKind::SList(vec![tag, inner]), span))
+}
 
-    ParseResult { parsed: SExpr::SSym(sym_slice), rest: rest_slice }
+// parse a `#`-prefixed atom off the token stream: `#t`/`#true`/`#f`/`#false`
+// booleans, or a `#\x`/`#\name`/`#\uXXXX` character literal
+fn hash_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let token = match tokens.next() {
+        Some(t) if t.kind == TokenKind::Sym && t.text.starts_with('#') => t,
+        Some(t) => return Err(ParseError::UnexpectedChar(t.text.chars().next().unwrap())),
+        None => return Err(ParseError::UnexpectedEof),
+    };
+
+    let kind = match token.text {
+        "#t" | "#true" => SExprKind::SBool(true),
+        "#f" | "#false" => SExprKind::SBool(false),
+        t if t.starts_with("#\\") => SExprKind::SChar(decode_char_name(&t[2..])?),
+        t => return Err(ParseError::BadChar(t.to_string())),
+    };
+
+    Ok(SExpr::new(kind, token.span))
 }
 
-// parse a string literal within double quotes off the input
-// this will NOT handle escaped quotes
-fn str_parse(s: &str) -> ParseResult {
-    // validate opening quote
-    if !s.starts_with('"') {
-        panic!("str_parse: expected double quote to start string, got: \"{}\"", s);
+// decode the name following `#\` into the character it denotes: a single
+// character, one of the named chars, or a \uXXXX-style code point
+fn decode_char_name(name: &str) -> Result<char, ParseError> {
+    match name {
+        "newline" => Ok('\n'),
+        "space" => Ok(' '),
+        "tab" => Ok('\t'),
+        _ if name.starts_with('u') && name.len() > 1 =>
+            u32::from_str_radix(&name[1..], 16).ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| ParseError::BadChar(format!("#\\{}", name))),
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(ParseError::BadChar(format!("#\\{}", name))),
+            }
+        },
     }
+}
 
-    // read from past the beginning quote, up to the ending quote
-    let (str_slice, rest_slice) = read_until(&s[1..], |c| {
-        c == '"'
-    });
+// parse a string literal within double quotes off the token stream, decoding
+// \" \\ \n \t \r and \uXXXX/\xHH hex escapes. unescaped strings stay
+// borrowed from the source; escaped ones are materialized into an owned
+// String once decoding is needed
+fn str_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let token = match tokens.next() {
+        Some(t) if matches!(t.kind, TokenKind::Str { .. }) => t,
+        Some(t) => return Err(ParseError::UnexpectedChar(t.text.chars().next().unwrap())),
+        None => return Err(ParseError::UnexpectedEof),
+    };
 
-    // validate closing quote
-    if !rest_slice.starts_with('"') {
-        panic!("str_parse: expected double quote to end string, got: \"{}\"", rest_slice);
+    if !matches!(token.kind, TokenKind::Str { terminated: true }) {
+        return Err(ParseError::UnterminatedString);
     }
 
-    // return parsed str and slice rest to ignore closing quote
-    ParseResult { parsed: SExpr::SStr(str_slice), rest: &rest_slice[1..] }
+    // strip the surrounding quotes before decoding escapes
+    let body = &token.text[1..token.text.len() - 1];
+    let content = decode_str_body(body)?;
+
+    Ok(SExpr::new(SExprKind::SStr(content), token.span))
 }
 
-// parse a list expression off the input
-fn list_parse(s: &str) -> ParseResult {
-    if !s.starts_with('(') {
-        panic!("list_parse: expected opening parenthesis, got: \"{}\"", s);
+// decode the escapes within a string literal's body (quotes already
+// stripped); borrows directly from the source when there's nothing to decode
+fn decode_str_body(body: &str) -> Result<Cow<'_, str>, ParseError> {
+    if !body.contains('\\') {
+        return Ok(Cow::Borrowed(body));
     }
 
-    let mut els = Vec::new();       // vector for accumulating list elements
-    let mut el;                     // holder for each element
-    let mut copy = eat_whitespace(&s[1..]); // eat the opening paren/whitespace
+    let mut buf = String::with_capacity(body.len());
+    let mut chars = body.char_indices().peekable();
 
-    // parse elements of list, with arbitrary whitespace in between
-    loop {
-        el = s_parse(copy);     // parse S-expression element
-        els.push(el.parsed);    // add to element list
-        copy = eat_whitespace(el.rest); // move to end of parsed input/eat trailing whitespace
-
-        if copy.is_empty() {
-            panic!("list_parse: unexpected end of list: no \
-            closing parenthesis found at \"{}\"", s);
+    while let Some((_, c)) = chars.next() {
+        if c != '\\' {
+            buf.push(c);
+            continue;
         }
 
-        // on end of list, break
-        if copy.starts_with(')') { break; }
+        let (_, esc) = chars.next().ok_or(ParseError::UnterminatedString)?;
+        match esc {
+            '"' => buf.push('"'),
+            '\\' => buf.push('\\'),
+            'n' => buf.push('\n'),
+            't' => buf.push('\t'),
+            'r' => buf.push('\r'),
+            'u' => buf.push(decode_hex_escape(&mut chars, 4, 'u')?),
+            'x' => buf.push(decode_hex_escape(&mut chars, 2, 'x')?),
+            other => return Err(ParseError::BadEscape(format!("\\{}", other))),
+        }
     }
 
-    copy = &copy[1..];  // eat the closing paren
-
-    ParseResult { parsed: SExpr::SList(els), rest: copy }
+    Ok(Cow::Owned(buf))
 }
 
-// advance str slice past leading whitespace, return reduced str
-fn eat_whitespace(mut s: &str) -> &str {
-    lazy_static! {
-        static ref WHITESPACE: Regex = Regex::new(r"\s").unwrap();
+// consume `n` hex digits from an escape sequence and decode them as a
+// unicode code point, for \uXXXX and \xHH escapes
+fn decode_hex_escape<I>(chars: &mut std::iter::Peekable<I>, n: usize, kind: char) -> Result<char, ParseError>
+    where I: Iterator<Item = (usize, char)> {
+
+    let mut hex = String::with_capacity(n);
+    for _ in 0..n {
+        match chars.next() {
+            Some((_, c)) if c.is_ascii_hexdigit() => hex.push(c),
+            _ => return Err(ParseError::BadEscape(format!("\\{}{}", kind, hex))),
+        }
     }
 
-    // predicate for detecting a whitespace char
-    let ws = |c: char| {
-        WHITESPACE.is_match(&c.to_string()) 
+    u32::from_str_radix(&hex, 16).ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| ParseError::BadEscape(format!("\\{}{}", kind, hex)))
+}
+
+// parse a list expression off the token stream
+fn list_parse<'a>(tokens: &mut Tokens<'a>) -> Result<SExpr<'a>, ParseError> {
+    let open = match tokens.next() {
+        Some(t) if t.kind == TokenKind::LParen => t,
+        Some(t) => return Err(ParseError::UnexpectedChar(t.text.chars().next().unwrap())),
+        None => return Err(ParseError::UnexpectedEof),
     };
-    
-    // eat arbitrary whitespace between elements
-    while !s.is_empty() && s.starts_with(ws) {
-        s = &s[1..];
+
+    let mut els = Vec::new();
+    skip_trivia(tokens)?;
+
+    // parse elements of list, with arbitrary whitespace and comments in between
+    loop {
+        match tokens.peek().copied() {
+            None => return Err(ParseError::UnterminatedList),
+            Some(Token { kind: TokenKind::RParen, .. }) => break,
+            Some(_) => {
+                els.push(s_parse(tokens)?);
+                skip_trivia(tokens)?;
+            },
+        }
+    }
+
+    let close = tokens.next().expect("checked by the loop above"); // the closing paren
+    let span = Span { start: open.span.start, end: close.span.end };
+
+    Ok(SExpr::new(SExprKind::SList(els), span))
+}
+
+// advance past leading whitespace and comments (line, nested block, and
+// datum comments); everything but the datum-comment case is just consuming
+// whatever the lexer hands back
+fn skip_trivia(tokens: &mut Tokens) -> Result<(), ParseError> {
+    loop {
+        match tokens.peek().copied() {
+            Some(Token { kind: TokenKind::Whitespace, .. }) => { tokens.next(); },
+            Some(Token { kind: TokenKind::Comment { terminated: true }, .. }) => { tokens.next(); },
+            Some(Token { kind: TokenKind::Comment { terminated: false }, .. }) =>
+                return Err(ParseError::UnterminatedComment),
+            Some(Token { kind: TokenKind::DatumComment, .. }) => {
+                tokens.next();             // consume the `#;` marker
+                s_parse(tokens)?;          // parse and discard the next expression
+            },
+            _ => break,
+        }
     }
 
-    return s;
+    Ok(())
 }
 
 
 #[cfg(test)]
 mod parse_tests {
     use super::*;
-    use SExpr::*;
+    use SExprKind::*;
+
+    // build an SExpr for comparison; span is ignored by equality so a
+    // placeholder is fine here
+    fn sx(kind: SExprKind) -> SExpr {
+        SExpr::new(kind, Span { start: 0, end: 0 })
+    }
+
+    // tokenize `s` for directly exercising a single parser helper
+    fn lex(s: &str) -> Tokens<'_> {
+        Lexer::new(s).peekable()
+    }
 
     /*---------------- parse tests ----------------*/
 
     #[test]
     fn parses_int() {
-        assert_eq!(parse("3"), vec![SInt(3)]);
-        assert_eq!(parse("193755"), vec![SInt(193755)]);
-        assert_eq!(parse("-1728"), vec![SInt(-1728)]);
-        assert_eq!(parse("1 5 -2"), vec![SInt(1), SInt(5), SInt(-2)]);
+        assert_eq!(parse("3"), Ok(vec![sx(SInt(3))]));
+        assert_eq!(parse("193755"), Ok(vec![sx(SInt(193755))]));
+        assert_eq!(parse("-1728"), Ok(vec![sx(SInt(-1728))]));
+        assert_eq!(parse("1 5 -2"), Ok(vec![sx(SInt(1)), sx(SInt(5)), sx(SInt(-2))]));
     }
 
     #[test]
     fn parses_float() {
-        assert_eq!(parse("0.5"), vec![SFloat(0.5)]);
-        assert_eq!(parse("-11.28"), vec![SFloat(-11.28)]);
-        assert_eq!(parse("34587.23424"), vec![SFloat(34587.23424)]);
-        assert_eq!(parse("0.5 2.1 8.32"), vec![SFloat(0.5), SFloat(2.1), SFloat(8.32)]);
+        assert_eq!(parse("0.5"), Ok(vec![sx(SFloat(0.5))]));
+        assert_eq!(parse("-11.28"), Ok(vec![sx(SFloat(-11.28))]));
+        assert_eq!(parse("34587.23424"), Ok(vec![sx(SFloat(34587.23424))]));
+        assert_eq!(parse("0.5 2.1 8.32"), Ok(vec![sx(SFloat(0.5)), sx(SFloat(2.1)), sx(SFloat(8.32))]));
     }
 
     #[test]
     fn parses_symbol() {
-        assert_eq!(parse("my-symbol"), vec![SSym("my-symbol")]);
-        assert_eq!(parse("x"), vec![SSym("x")]);
-        assert_eq!(parse("NAME"), vec![SSym("NAME")]);
-        assert_eq!(parse("e^2*x/y"), vec![SSym("e^2*x/y")]);
-        assert_eq!(parse("x y z"), vec![SSym("x"), SSym("y"), SSym("z")]);
+        assert_eq!(parse("my-symbol"), Ok(vec![sx(SSym("my-symbol"))]));
+        assert_eq!(parse("x"), Ok(vec![sx(SSym("x"))]));
+        assert_eq!(parse("NAME"), Ok(vec![sx(SSym("NAME"))]));
+        assert_eq!(parse("e^2*x/y"), Ok(vec![sx(SSym("e^2*x/y"))]));
+        assert_eq!(parse("x y z"), Ok(vec![sx(SSym("x")), sx(SSym("y")), sx(SSym("z"))]));
     }
 
     #[test]
     fn parses_str() {
-        assert_eq!(parse("\"test\""), vec![SStr("test")]);
-        assert_eq!(parse("\"this is a string\""), vec![SStr("this is a string")]);
-        assert_eq!(parse("\"23847\""), vec![SStr("23847")]);
-        assert_eq!(parse("\"(parens)\""), vec![SStr("(parens)")]);
-        assert_eq!(parse("\"one\" \"two\""), vec![SStr("one"), SStr("two")]);
+        assert_eq!(parse("\"test\""), Ok(vec![sx(SStr("test".into()))]));
+        assert_eq!(parse("\"this is a string\""), Ok(vec![sx(SStr("this is a string".into()))]));
+        assert_eq!(parse("\"23847\""), Ok(vec![sx(SStr("23847".into()))]));
+        assert_eq!(parse("\"(parens)\""), Ok(vec![sx(SStr("(parens)".into()))]));
+        assert_eq!(parse("\"one\" \"two\""), Ok(vec![sx(SStr("one".into())), sx(SStr("two".into()))]));
+    }
+
+    #[test]
+    fn parses_bool() {
+        assert_eq!(parse("#t"), Ok(vec![sx(SBool(true))]));
+        assert_eq!(parse("#true"), Ok(vec![sx(SBool(true))]));
+        assert_eq!(parse("#f"), Ok(vec![sx(SBool(false))]));
+        assert_eq!(parse("#false"), Ok(vec![sx(SBool(false))]));
+        assert_eq!(parse("#t #f"), Ok(vec![sx(SBool(true)), sx(SBool(false))]));
+    }
+
+    #[test]
+    fn parses_char() {
+        assert_eq!(parse("#\\a"), Ok(vec![sx(SChar('a'))]));
+        assert_eq!(parse("#\\newline"), Ok(vec![sx(SChar('\n'))]));
+        assert_eq!(parse("#\\space"), Ok(vec![sx(SChar(' '))]));
+        assert_eq!(parse("#\\tab"), Ok(vec![sx(SChar('\t'))]));
+        assert_eq!(parse("#\\u0041"), Ok(vec![sx(SChar('A'))]));
+    }
+
+    #[test]
+    fn reports_bad_chars() {
+        assert!(matches!(parse("#\\nonsense"), Err(ParseError::BadChar(_))));
+        assert!(matches!(parse("#nonsense"), Err(ParseError::BadChar(_))));
     }
 
     #[test]
     fn parses_list() {
         assert_eq!(
-            parse("(1 2 3)"), 
-            vec![SList(vec![SInt(1), SInt(2), SInt(3)])]);
+            parse("(1 2 3)"),
+            Ok(vec![sx(SList(vec![sx(SInt(1)), sx(SInt(2)), sx(SInt(3))]))]));
         assert_eq!(
-            parse("(name)"), 
-            vec![SList(vec![SSym("name")])]);
+            parse("(name)"),
+            Ok(vec![sx(SList(vec![sx(SSym("name"))]))]));
         assert_eq!(
-            parse("(f \"arg\" 2 5)"), 
-            vec![SList(vec![SSym("f"), SStr("arg"), SInt(2), SInt(5)])]);
+            parse("(f \"arg\" 2 5)"),
+            Ok(vec![sx(SList(vec![sx(SSym("f")), sx(SStr("arg".into())), sx(SInt(2)), sx(SInt(5))]))]));
         assert_eq!(
             parse("(a b) (c d)"),
-            vec![SList(vec![SSym("a"), SSym("b")]), SList(vec![SSym("c"), SSym("d")])]);
+            Ok(vec![sx(SList(vec![sx(SSym("a")), sx(SSym("b"))])), sx(SList(vec![sx(SSym("c")), sx(SSym("d"))]))]));
     }
 
     #[test]
@@ -250,108 +402,221 @@ mod parse_tests {
             parse(" (define (f x y) \
                         (* x (+ 2 y))) \
                     (f -3 2.7)"),
-            vec![
-                SList(vec![SSym("define"), 
-                    SList(vec![SSym("f"), SSym("x"), SSym("y")]),
-                    SList(vec![SSym("*"), SSym("x"),
-                        SList(vec![SSym("+"), SInt(2), SSym("y")])])]),
-                SList(vec![SSym("f"), SInt(-3), SFloat(2.7)])]);
-        
+            Ok(vec![
+                sx(SList(vec![sx(SSym("define")),
+                    sx(SList(vec![sx(SSym("f")), sx(SSym("x")), sx(SSym("y"))])),
+                    sx(SList(vec![sx(SSym("*")), sx(SSym("x")),
+                        sx(SList(vec![sx(SSym("+")), sx(SInt(2)), sx(SSym("y"))]))]))])),
+                sx(SList(vec![sx(SSym("f")), sx(SInt(-3)), sx(SFloat(2.7))]))]));
+
         // ignores whitespace
         assert_eq!(
             parse("    (  f   105   xyz ) "),
-            vec![SList(vec![SSym("f"), SInt(105), SSym("xyz")])]);
+            Ok(vec![sx(SList(vec![sx(SSym("f")), sx(SInt(105)), sx(SSym("xyz"))]))]));
         assert_eq!(
             parse("     "),
-            vec![]);
+            Ok(vec![]));
 
         assert_eq!(
             parse("(f \"test string\" 100)"),
-            vec![SList(vec![SSym("f"), SStr("test string"), SInt(100)])]);
+            Ok(vec![sx(SList(vec![sx(SSym("f")), sx(SStr("test string".into())), sx(SInt(100))]))]));
     }
 
-    /*---------------- tests for parsing helpers ----------------*/
+    #[test]
+    fn reports_unterminated_string() {
+        assert_eq!(parse("\"no closing quote"), Err(ParseError::UnterminatedString));
+    }
+
+    /*---------------- escape tests ----------------*/
 
     #[test]
-    fn test_s_parse() {
-        // s_parse can parse an expression of any type
+    fn decodes_escaped_quotes_and_backslashes() {
+        assert_eq!(parse("\"she said \\\"hi\\\"\""), Ok(vec![sx(SStr("she said \"hi\"".into()))]));
+        assert_eq!(parse("\"back\\\\slash\""), Ok(vec![sx(SStr("back\\slash".into()))]));
+    }
+
+    #[test]
+    fn decodes_escaped_whitespace_chars() {
+        assert_eq!(parse("\"line\\nbreak\""), Ok(vec![sx(SStr("line\nbreak".into()))]));
+        assert_eq!(parse("\"a\\tb\""), Ok(vec![sx(SStr("a\tb".into()))]));
+        assert_eq!(parse("\"a\\rb\""), Ok(vec![sx(SStr("a\rb".into()))]));
+    }
+
+    #[test]
+    fn decodes_hex_escapes() {
+        assert_eq!(parse("\"\\u0041\\u0042\""), Ok(vec![sx(SStr("AB".into()))]));
+        assert_eq!(parse("\"\\x41\""), Ok(vec![sx(SStr("A".into()))]));
+    }
+
+    #[test]
+    fn reports_bad_escapes() {
+        assert!(matches!(parse("\"\\q\""), Err(ParseError::BadEscape(_))));
+        assert!(matches!(parse("\"\\u12\""), Err(ParseError::BadEscape(_))));
+    }
+
+    #[test]
+    fn reports_unterminated_list() {
+        assert_eq!(parse("(a b"), Err(ParseError::UnterminatedList));
+    }
+
+    #[test]
+    fn reports_unexpected_eof() {
+        assert_eq!(parse(""), Ok(vec![]));
+        assert_eq!(s_parse(&mut lex("")), Err(ParseError::UnexpectedEof));
+    }
+
+    /*---------------- comment tests ----------------*/
+
+    #[test]
+    fn skips_line_comments() {
+        assert_eq!(parse("; a comment\n1"), Ok(vec![sx(SInt(1))]));
+        assert_eq!(parse("1 ; trailing comment"), Ok(vec![sx(SInt(1))]));
         assert_eq!(
-            s_parse("100.05"),
-            ParseResult { parsed: SFloat(100.05), rest: "" });
+            parse("(a ; comment inside a list\n b)"),
+            Ok(vec![sx(SList(vec![sx(SSym("a")), sx(SSym("b"))]))]));
+    }
+
+    #[test]
+    fn skips_block_comments() {
+        assert_eq!(parse("#| a block comment |# 1"), Ok(vec![sx(SInt(1))]));
         assert_eq!(
-            s_parse("75"),
-            ParseResult { parsed: SInt(75), rest: "" });
+            parse("#| outer #| nested |# still outer |# 1"),
+            Ok(vec![sx(SInt(1))]));
         assert_eq!(
-            s_parse("symbol"),
-            ParseResult { parsed: SSym("symbol"), rest: "" });
+            parse("#| unterminated"), Err(ParseError::UnterminatedComment));
+    }
+
+    #[test]
+    fn skips_datum_comments() {
+        assert_eq!(parse("#;ignored 1"), Ok(vec![sx(SInt(1))]));
         assert_eq!(
-            s_parse("\"string\""),
-            ParseResult { parsed: SStr("string"), rest: "" });
+            parse("(a #;(ignored list) b)"),
+            Ok(vec![sx(SList(vec![sx(SSym("a")), sx(SSym("b"))]))]));
+        assert_eq!(parse("#;1 2"), Ok(vec![sx(SInt(2))]));
+    }
+
+    /*---------------- reader macro tests ----------------*/
+
+    #[test]
+    fn desugars_quote() {
+        assert_eq!(parse("'x"), Ok(vec![sx(SList(vec![sx(SSym("quote")), sx(SSym("x"))]))]));
         assert_eq!(
-            s_parse("(list of els)"),
-            ParseResult {
-                parsed: SList(vec![SSym("list"), SSym("of"), SSym("els")]), 
-                rest: "" });
+            parse("'(a b)"),
+            Ok(vec![sx(SList(vec![sx(SSym("quote")),
+                sx(SList(vec![sx(SSym("a")), sx(SSym("b"))]))]))]));
     }
 
     #[test]
-    fn test_num_parse() {
-        // parsing numeric expressions
+    fn desugars_quasiquote_and_unquote() {
+        assert_eq!(parse("`x"), Ok(vec![sx(SList(vec![sx(SSym("quasiquote")), sx(SSym("x"))]))]));
+        assert_eq!(parse(",x"), Ok(vec![sx(SList(vec![sx(SSym("unquote")), sx(SSym("x"))]))]));
         assert_eq!(
-            num_parse("-17.182 x y z)"),
-            ParseResult { parsed: SFloat(-17.182), rest: " x y z)" });
+            parse(",@x"),
+            Ok(vec![sx(SList(vec![sx(SSym("unquote-splicing")), sx(SSym("x"))]))]));
         assert_eq!(
-            num_parse("6)"),
-            ParseResult { parsed: SInt(6), rest: ")" });
+            parse("`(a ,b ,@c)"),
+            Ok(vec![sx(SList(vec![sx(SSym("quasiquote")),
+                sx(SList(vec![
+                    sx(SSym("a")),
+                    sx(SList(vec![sx(SSym("unquote")), sx(SSym("b"))])),
+                    sx(SList(vec![sx(SSym("unquote-splicing")), sx(SSym("c"))])),
+                ]))]))]));
+    }
+
+    #[test]
+    fn desugars_with_trivia_before_the_datum() {
+        assert_eq!(parse("' x"), Ok(vec![sx(SList(vec![sx(SSym("quote")), sx(SSym("x"))]))]));
+        assert_eq!(
+            parse("` ; comment\n (a , b)"),
+            Ok(vec![sx(SList(vec![sx(SSym("quasiquote")),
+                sx(SList(vec![sx(SSym("a")),
+                    sx(SList(vec![sx(SSym("unquote")), sx(SSym("b"))]))]))]))]));
+    }
+
+    /*---------------- span tests ----------------*/
+
+    #[test]
+    fn tracks_spans_of_atoms() {
+        let exprs = parse("foo 42").unwrap();
+        assert_eq!(exprs[0].get_span(), Span { start: 0, end: 3 });
+        assert_eq!(exprs[1].get_span(), Span { start: 4, end: 6 });
+    }
+
+    #[test]
+    fn tracks_spans_of_lists() {
+        let exprs = parse("(foo 42)").unwrap();
+        assert_eq!(exprs[0].get_span(), Span { start: 0, end: 8 });
+
+        match exprs[0].kind() {
+            SList(els) => {
+                assert_eq!(els[0].get_span(), Span { start: 1, end: 4 });
+                assert_eq!(els[1].get_span(), Span { start: 5, end: 7 });
+            },
+            _ => panic!("expected a list"),
+        }
+    }
+
+    #[test]
+    fn tracks_spans_after_leading_whitespace() {
+        let exprs = parse("  (a b)  (c)").unwrap();
+        assert_eq!(exprs[0].get_span(), Span { start: 2, end: 7 });
+        assert_eq!(exprs[1].get_span(), Span { start: 9, end: 12 });
+    }
+
+    /*---------------- tests for parsing helpers ----------------*/
+
+    #[test]
+    fn test_s_parse() {
+        // s_parse can parse an expression of any type
+        assert_eq!(s_parse(&mut lex("100.05")), Ok(sx(SFloat(100.05))));
+        assert_eq!(s_parse(&mut lex("75")), Ok(sx(SInt(75))));
+        assert_eq!(s_parse(&mut lex("symbol")), Ok(sx(SSym("symbol"))));
+        assert_eq!(s_parse(&mut lex("\"string\"")), Ok(sx(SStr("string".into()))));
         assert_eq!(
-            num_parse("100"),
-            ParseResult { parsed: SInt(100), rest: "" });
+            s_parse(&mut lex("(list of els)")),
+            Ok(sx(SList(vec![sx(SSym("list")), sx(SSym("of")), sx(SSym("els"))]))));
+    }
+
+    #[test]
+    fn test_num_parse() {
+        // parsing numeric expressions
+        let mut tokens = lex("-17.182 x y z)");
+        assert_eq!(num_parse(&mut tokens), Ok(sx(SFloat(-17.182))));
+        skip_trivia(&mut tokens).unwrap();
+        assert_eq!(s_parse(&mut tokens), Ok(sx(SSym("x"))));
+
+        assert_eq!(num_parse(&mut lex("6)")), Ok(sx(SInt(6))));
+        assert_eq!(num_parse(&mut lex("100")), Ok(sx(SInt(100))));
     }
 
     #[test]
     fn test_sym_parse() {
         // parsing symbols
-        assert_eq!(
-            sym_parse("symbol-name/here next)"),
-            ParseResult { parsed: SSym("symbol-name/here"), rest: " next)" });
-        assert_eq!(
-            sym_parse("name-with-nums1283)"),
-            ParseResult { parsed: SSym("name-with-nums1283"), rest: ")" });
-        assert_eq!(
-            sym_parse("terminal"),
-            ParseResult { parsed: SSym("terminal"), rest: "" });
+        assert_eq!(sym_parse(&mut lex("symbol-name/here")), Ok(sx(SSym("symbol-name/here"))));
+        assert_eq!(sym_parse(&mut lex("name-with-nums1283)")), Ok(sx(SSym("name-with-nums1283"))));
+        assert_eq!(sym_parse(&mut lex("terminal")), Ok(sx(SSym("terminal"))));
     }
 
     #[test]
     fn test_str_parse() {
         // parsing strings
-        assert_eq!(
-            str_parse("\"string value inside!\""),
-            ParseResult { parsed: SStr("string value inside!"), rest: "" });
-        assert_eq!(
-            str_parse("\"first\" next-sym)"),
-            ParseResult { parsed: SStr("first"), rest: " next-sym)" });
-        assert_eq!(
-            str_parse("\"\" 1 5"),
-            ParseResult { parsed: SStr(""), rest: " 1 5" });
+        assert_eq!(str_parse(&mut lex("\"string value inside!\"")), Ok(sx(SStr("string value inside!".into()))));
+        assert_eq!(str_parse(&mut lex("\"first\" next-sym)")), Ok(sx(SStr("first".into()))));
+        assert_eq!(str_parse(&mut lex("\"\" 1 5")), Ok(sx(SStr("".into()))));
     }
 
     #[test]
     fn test_list_parse() {
         // parsing list expressions
         assert_eq!(
-            list_parse("(a 1 \"c\")"),
-            ParseResult { parsed: SList(vec![SSym("a"), SInt(1), SStr("c")]), rest: "" });
+            list_parse(&mut lex("(a 1 \"c\")")),
+            Ok(sx(SList(vec![sx(SSym("a")), sx(SInt(1)), sx(SStr("c".into()))]))));
         assert_eq!(
-            list_parse("(name (list within list))"),
-            ParseResult { 
-                parsed: SList(vec![SSym("name"), SList(vec![SSym("list"), SSym("within"), SSym("list")])]), 
-                rest: "" });
+            list_parse(&mut lex("(name (list within list))")),
+            Ok(sx(SList(vec![sx(SSym("name")), sx(SList(vec![sx(SSym("list")), sx(SSym("within")), sx(SSym("list"))]))]))));
         assert_eq!(
-            list_parse("( spacing     does not    matter  )"),
-            ParseResult { 
-                parsed: SList(vec![SSym("spacing"), SSym("does"), SSym("not"), SSym("matter")]), 
-                rest: "" });
+            list_parse(&mut lex("( spacing     does not    matter  )")),
+            Ok(sx(SList(vec![sx(SSym("spacing")), sx(SSym("does")), sx(SSym("not")), sx(SSym("matter"))]))));
     }
 
-}
\ No newline at end of file
+}